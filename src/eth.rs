@@ -23,6 +23,29 @@ pub const SECP256K1_SIGNATURE_BYTES_LEN: usize = 65;
 pub const HASH_BYTES_LEN: usize = 32;
 pub const ADDR_BYTES_LEN: usize = 20;
 
+// Public key encodings this crate accepts: the raw 64-byte x||y body used
+// internally throughout the crate, the 65-byte SEC1 uncompressed form
+// (0x04 prefix + raw body), and the 33-byte SEC1 compressed form.
+pub const ECDSA_RAW_PUBKEY_LEN: usize = 64;
+pub const ECDSA_UNCOMPRESSED_PUBKEY_LEN: usize = 65;
+pub const ECDSA_COMPRESSED_PUBKEY_LEN: usize = 33;
+
+// Half of the secp256k1 curve order n, i.e. n/2. `secp256k1_sign` only ever
+// produces signatures with S <= this value (see `sign_ecdsa_recoverable`'s
+// built-in low-S normalization), so a signature with a larger S is a
+// malleable re-encoding of one we would have produced and must be rejected.
+const SECP256K1_HALF_N: [u8; HASH_BYTES_LEN] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+// Reject non-canonical (high-S) signatures: for every valid signature there
+// are two S values, s and n-s, and only the smaller one is accepted so that a
+// given message/key pair has exactly one valid wire encoding.
+fn is_signature_s_too_high(signature: &[u8]) -> bool {
+    signature[32..SECP256K1_SIGNATURE_BYTES_LEN - 1] > SECP256K1_HALF_N[..]
+}
+
 fn keccak_hash(input: &[u8]) -> [u8; HASH_BYTES_LEN] {
     let mut result = [0u8; HASH_BYTES_LEN];
     let mut keccak = Keccak::v256();
@@ -31,16 +54,46 @@ fn keccak_hash(input: &[u8]) -> [u8; HASH_BYTES_LEN] {
     result
 }
 
+// Breaking change: the single `pub static SECP256K1: Secp256k1<All>` this
+// crate used to expose is gone, split into `SECP256K1_SIGN`/`SECP256K1_VERIFY`
+// below so a verifier-only node doesn't pay for signing tables it never uses.
+// Any external crate referencing `eth::SECP256K1` directly needs to switch to
+// whichever of the two matches its use (or `secp256k1::Secp256k1::new()` if it
+// genuinely needs both capabilities).
 lazy_static::lazy_static! {
-    pub static ref SECP256K1: secp256k1::Secp256k1<secp256k1::All> = secp256k1::Secp256k1::new();
+    // Only the signing tables are needed to produce a signature or derive a
+    // public key from a secret key, so `sk2pk`/`secp256k1_sign` use this
+    // instead of the all-caps context.
+    pub static ref SECP256K1_SIGN: secp256k1::Secp256k1<secp256k1::SignOnly> =
+        secp256k1::Secp256k1::signing_only();
+    // Only the verification tables are needed to recover a public key from a
+    // signature, so `secp256k1_recover` uses this instead of the all-caps
+    // context. Parsing-only operations (`RecoveryId::try_from`,
+    // `RecoverableSignature::from_compact`, `Message::from_digest_slice`)
+    // take no context at all and need neither table.
+    pub static ref SECP256K1_VERIFY: secp256k1::Secp256k1<secp256k1::VerificationOnly> =
+        secp256k1::Secp256k1::verification_only();
+}
+
+// Load a private key, rejecting malformed/out-of-range bytes with a clean
+// error instead of panicking (wrong length, zero, or >= the curve order).
+// Callers pass the error to report on failure, since an invalid key means
+// different things depending on what's being attempted: failing to sign is
+// a `SignError`, but failing to merely derive a public key/address from a
+// key that was never going to sign anything is a `SigCheckError`.
+fn load_secret_key(
+    privkey: &[u8],
+    err: StatusCodeEnum,
+) -> Result<secp256k1::SecretKey, StatusCodeEnum> {
+    secp256k1::SecretKey::from_slice(privkey).map_err(|_| err)
 }
 
 fn secp256k1_sign(
     privkey: &[u8],
     msg: &[u8],
 ) -> Result<[u8; SECP256K1_SIGNATURE_BYTES_LEN], StatusCodeEnum> {
-    let context = &SECP256K1;
-    let sec = secp256k1::SecretKey::from_slice(privkey).unwrap();
+    let context = &SECP256K1_SIGN;
+    let sec = load_secret_key(privkey, StatusCodeEnum::SignError)?;
     if let Ok(message) = secp256k1::Message::from_digest_slice(msg) {
         let s = context.sign_ecdsa_recoverable(&message, &sec);
         let (rec_id, data) = s.serialize_compact();
@@ -56,8 +109,15 @@ fn secp256k1_sign(
     }
 }
 
-fn secp256k1_recover(signature: &[u8], message: &[u8]) -> Result<Vec<u8>, StatusCodeEnum> {
-    let context = &SECP256K1;
+fn secp256k1_recover_pubkey(
+    signature: &[u8],
+    message: &[u8],
+) -> Result<secp256k1::PublicKey, StatusCodeEnum> {
+    if is_signature_s_too_high(signature) {
+        return Err(StatusCodeEnum::SigCheckError);
+    }
+
+    let context = &SECP256K1_VERIFY;
     if let Ok(rid) =
         secp256k1::ecdsa::RecoveryId::try_from(signature[SECP256K1_SIGNATURE_BYTES_LEN - 1] as i32)
     {
@@ -67,8 +127,7 @@ fn secp256k1_recover(signature: &[u8], message: &[u8]) -> Result<Vec<u8>, Status
         ) {
             if let Ok(msg) = secp256k1::Message::from_digest_slice(message) {
                 if let Ok(publ) = context.recover_ecdsa(&msg, &rsig) {
-                    let serialized = publ.serialize_uncompressed();
-                    return Ok(serialized[1..65].to_vec());
+                    return Ok(publ);
                 }
             }
         }
@@ -77,6 +136,19 @@ fn secp256k1_recover(signature: &[u8], message: &[u8]) -> Result<Vec<u8>, Status
     Err(StatusCodeEnum::SigCheckError)
 }
 
+fn secp256k1_recover(signature: &[u8], message: &[u8]) -> Result<Vec<u8>, StatusCodeEnum> {
+    let publ = secp256k1_recover_pubkey(signature, message)?;
+    Ok(publ.serialize_uncompressed()[1..].to_vec())
+}
+
+fn secp256k1_recover_compressed(
+    signature: &[u8],
+    message: &[u8],
+) -> Result<Vec<u8>, StatusCodeEnum> {
+    let publ = secp256k1_recover_pubkey(signature, message)?;
+    Ok(publ.serialize().to_vec())
+}
+
 pub fn hash_data(data: &[u8]) -> Vec<u8> {
     keccak_hash(data).to_vec()
 }
@@ -91,22 +163,73 @@ pub fn verify_data_hash(data: &[u8], hash: &[u8]) -> Result<(), StatusCodeEnum>
     }
 }
 
-pub fn sk2pk(sk: &[u8]) -> Vec<u8> {
-    let context = &SECP256K1;
-    let sec = secp256k1::SecretKey::from_slice(sk).unwrap();
+// Breaking change: this used to return `Vec<u8>` and panic on an invalid
+// secret key; it now returns `Result` so callers can handle a bad key
+// themselves. Any external caller needs to add a `?` or match on the error.
+pub fn sk2pk(sk: &[u8]) -> Result<Vec<u8>, StatusCodeEnum> {
+    let context = &SECP256K1_SIGN;
+    let sec = load_secret_key(sk, StatusCodeEnum::SigCheckError)?;
     let pub_key = secp256k1::PublicKey::from_secret_key(context, &sec);
     let serialized = pub_key.serialize_uncompressed();
-    serialized[1..].to_vec()
+    Ok(serialized[1..].to_vec())
+}
+
+// Breaking change: see `sk2pk` above.
+pub fn sk2pk_compressed(sk: &[u8]) -> Result<Vec<u8>, StatusCodeEnum> {
+    let context = &SECP256K1_SIGN;
+    let sec = load_secret_key(sk, StatusCodeEnum::SigCheckError)?;
+    let pub_key = secp256k1::PublicKey::from_secret_key(context, &sec);
+    Ok(pub_key.serialize().to_vec())
 }
 
+// Breaking change: see `sk2pk` above.
 #[allow(dead_code)]
-pub fn sk2address(sk: &[u8]) -> Vec<u8> {
-    let pk = sk2pk(sk);
+pub fn sk2address(sk: &[u8]) -> Result<Vec<u8>, StatusCodeEnum> {
+    let pk = sk2pk(sk)?;
     pk2address(&pk)
 }
 
-pub fn pk2address(pk: &[u8]) -> Vec<u8> {
-    hash_data(pk)[HASH_BYTES_LEN - ADDR_BYTES_LEN..].to_vec()
+// Decode any of the raw/compressed/uncompressed public key encodings into
+// the raw 64-byte x||y body used for address derivation.
+fn pk2raw(pk: &[u8]) -> Result<Vec<u8>, StatusCodeEnum> {
+    match pk.len() {
+        ECDSA_RAW_PUBKEY_LEN => Ok(pk.to_vec()),
+        ECDSA_UNCOMPRESSED_PUBKEY_LEN => Ok(pk[1..].to_vec()),
+        ECDSA_COMPRESSED_PUBKEY_LEN => {
+            let public =
+                secp256k1::PublicKey::from_slice(pk).map_err(|_| StatusCodeEnum::SigCheckError)?;
+            Ok(public.serialize_uncompressed()[1..].to_vec())
+        }
+        _ => Err(StatusCodeEnum::SigCheckError),
+    }
+}
+
+// Breaking change: this used to return `Vec<u8>` unconditionally; now that it
+// also accepts the compressed encoding, decompression can fail, so it
+// returns `Result`. Any external caller needs to add a `?` or match on the
+// error.
+pub fn pk2address(pk: &[u8]) -> Result<Vec<u8>, StatusCodeEnum> {
+    let raw = pk2raw(pk)?;
+    Ok(hash_data(&raw)[HASH_BYTES_LEN - ADDR_BYTES_LEN..].to_vec())
+}
+
+// Parse any of the raw/compressed/uncompressed encodings into a curve point,
+// for operations (like ECDH) that need the point itself rather than just its
+// hash.
+fn parse_pubkey(pk: &[u8]) -> Result<secp256k1::PublicKey, StatusCodeEnum> {
+    match pk.len() {
+        ECDSA_COMPRESSED_PUBKEY_LEN | ECDSA_UNCOMPRESSED_PUBKEY_LEN => {
+            secp256k1::PublicKey::from_slice(pk).map_err(|_| StatusCodeEnum::SigCheckError)
+        }
+        ECDSA_RAW_PUBKEY_LEN => {
+            let mut uncompressed = [0u8; ECDSA_UNCOMPRESSED_PUBKEY_LEN];
+            uncompressed[0] = 0x04;
+            uncompressed[1..].copy_from_slice(pk);
+            secp256k1::PublicKey::from_slice(&uncompressed)
+                .map_err(|_| StatusCodeEnum::SigCheckError)
+        }
+        _ => Err(StatusCodeEnum::SigCheckError),
+    }
 }
 
 pub fn sign_message(_pubkey: &[u8], privkey: &[u8], msg: &[u8]) -> Result<Vec<u8>, StatusCodeEnum> {
@@ -121,6 +244,100 @@ pub fn recover_signature(msg: &[u8], signature: &[u8]) -> Result<Vec<u8>, Status
     }
 }
 
+// Same as `recover_signature`, but returns the recovered key in the 33-byte
+// SEC1 compressed encoding instead of the raw 64-byte body.
+pub fn recover_signature_compressed(
+    msg: &[u8],
+    signature: &[u8],
+) -> Result<Vec<u8>, StatusCodeEnum> {
+    if signature.len() != SECP256K1_SIGNATURE_BYTES_LEN {
+        Err(StatusCodeEnum::SigLenError)
+    } else {
+        secp256k1_recover_compressed(signature, msg)
+    }
+}
+
+// Decode an Ethereum-style `v` into our internal bare recovery id (0/1).
+// With `chain_id: None`, `v` is either already a bare recid or the legacy
+// 27/28 encoding. With `chain_id: Some(_)`, `v` must be the EIP-155
+// `35 + 2*chain_id + recid` encoding. `v` is taken as `u64`, not a single
+// byte, because EIP-155 `v` grows with the chain id and overflows a byte
+// well within chain ids used in practice (any chain id >= 111 already pushes
+// `35 + 2*chain_id` past 255).
+fn decode_recid(v: u64, chain_id: Option<u64>) -> Result<u8, StatusCodeEnum> {
+    match chain_id {
+        Some(chain_id) => {
+            let base = 35 + 2 * chain_id;
+            v.checked_sub(base)
+                .filter(|&recid| recid <= 1)
+                .map(|recid| recid as u8)
+                .ok_or(StatusCodeEnum::SigCheckError)
+        }
+        None => match v {
+            0 | 1 => Ok(v as u8),
+            27 | 28 => Ok((v - 27) as u8),
+            _ => Err(StatusCodeEnum::SigCheckError),
+        },
+    }
+}
+
+// Build our internal r||s||recid signature encoding from an Ethereum-style
+// r/s/v triple, accepting both the bare recovery id (0/1) and the legacy
+// 27/28 encoding. EIP-155 `v` values must go through `recover_signature_eth`
+// instead, since decoding them needs the chain id.
+pub fn signature_from_rsv(
+    r: &[u8; HASH_BYTES_LEN],
+    s: &[u8; HASH_BYTES_LEN],
+    v: u8,
+) -> Result<[u8; SECP256K1_SIGNATURE_BYTES_LEN], StatusCodeEnum> {
+    let recid = decode_recid(v as u64, None)?;
+
+    let mut signature = [0u8; SECP256K1_SIGNATURE_BYTES_LEN];
+    signature[0..HASH_BYTES_LEN].copy_from_slice(r);
+    signature[HASH_BYTES_LEN..SECP256K1_SIGNATURE_BYTES_LEN - 1].copy_from_slice(s);
+    signature[SECP256K1_SIGNATURE_BYTES_LEN - 1] = recid;
+    Ok(signature)
+}
+
+// The inverse of `signature_from_rsv`: split our internal signature encoding
+// back into r/s and a legacy (27/28) `v`, the encoding understood by plain
+// Ethereum tooling with no chain id involved.
+pub fn signature_to_rsv(
+    signature: &[u8],
+) -> Result<([u8; HASH_BYTES_LEN], [u8; HASH_BYTES_LEN], u8), StatusCodeEnum> {
+    if signature.len() != SECP256K1_SIGNATURE_BYTES_LEN {
+        return Err(StatusCodeEnum::SigLenError);
+    }
+
+    let mut r = [0u8; HASH_BYTES_LEN];
+    let mut s = [0u8; HASH_BYTES_LEN];
+    r.copy_from_slice(&signature[0..HASH_BYTES_LEN]);
+    s.copy_from_slice(&signature[HASH_BYTES_LEN..SECP256K1_SIGNATURE_BYTES_LEN - 1]);
+    let v = signature[SECP256K1_SIGNATURE_BYTES_LEN - 1] + 27;
+    Ok((r, s, v))
+}
+
+// Recover a public key from an Ethereum-encoded r/s/v signature whose `v` is
+// either legacy (27/28, or a bare 0/1 recid, when `chain_id` is `None`) or EIP-155
+// (`35 + 2*chain_id + recid`, when `chain_id` is `Some`). `v` is taken
+// out-of-band as a `u64` rather than packed into a 65-byte signature, since
+// EIP-155 `v` does not fit in a single byte once `chain_id` is large.
+pub fn recover_signature_eth(
+    msg: &[u8],
+    r: &[u8; HASH_BYTES_LEN],
+    s: &[u8; HASH_BYTES_LEN],
+    v: u64,
+    chain_id: Option<u64>,
+) -> Result<Vec<u8>, StatusCodeEnum> {
+    let recid = decode_recid(v, chain_id)?;
+
+    let mut signature = [0u8; SECP256K1_SIGNATURE_BYTES_LEN];
+    signature[0..HASH_BYTES_LEN].copy_from_slice(r);
+    signature[HASH_BYTES_LEN..SECP256K1_SIGNATURE_BYTES_LEN - 1].copy_from_slice(s);
+    signature[SECP256K1_SIGNATURE_BYTES_LEN - 1] = recid;
+    secp256k1_recover(&signature, msg)
+}
+
 pub fn crypto_check_batch(raw_txs: &RawTransactions) -> StatusCodeEnum {
     use rayon::prelude::*;
 
@@ -171,7 +388,7 @@ pub fn crypto_check(raw_tx: &RawTransaction) -> Result<(), StatusCodeEnum> {
 
             verify_data_hash(&tx_bytes, tx_hash)?;
 
-            if &pk2address(&recover_signature(tx_hash, signature)?) == sender {
+            if &pk2address(&recover_signature(tx_hash, signature)?)? == sender {
                 Ok(())
             } else {
                 warn!("crypto_check failed: sig check error");
@@ -205,7 +422,7 @@ pub fn crypto_check(raw_tx: &RawTransaction) -> Result<(), StatusCodeEnum> {
                 let signature = &w.signature;
                 let sender = &w.sender;
 
-                if &pk2address(&recover_signature(tx_hash, signature)?) != sender {
+                if &pk2address(&recover_signature(tx_hash, signature)?)? != sender {
                     warn!("crypto_check failed: sig check error");
                     return Err(StatusCodeEnum::SigCheckError);
                 }
@@ -216,6 +433,136 @@ pub fn crypto_check(raw_tx: &RawTransaction) -> Result<(), StatusCodeEnum> {
     }
 }
 
+/// ECIES encryption of confidential payloads to a secp256k1 public key.
+///
+/// The wire format is `ephemeral_pubkey (65, uncompressed) || iv (16) ||
+/// ciphertext || mac (32)`. A fresh ephemeral keypair is generated per
+/// message; its public half is shipped alongside the ciphertext so the
+/// recipient can redo the ECDH with their own secret key. The shared secret
+/// is run through a keccak-based KDF to split it into an independent
+/// encryption key and MAC key, following the encrypt-then-MAC construction
+/// (the MAC covers the iv and ciphertext, so tampering is caught before
+/// decryption).
+pub mod ecies {
+    use super::{
+        keccak_hash, load_secret_key, parse_pubkey, ECDSA_UNCOMPRESSED_PUBKEY_LEN, HASH_BYTES_LEN,
+        SECP256K1_SIGN,
+    };
+    use cita_cloud_proto::status_code::StatusCodeEnum;
+    use rand::RngCore;
+
+    const IV_BYTES_LEN: usize = 16;
+    const MAC_BYTES_LEN: usize = 32;
+
+    // Derive an encryption key and a MAC key from the ECDH shared secret (the
+    // SHA-256-hashed output of `secp256k1::ecdh::SharedSecret`),
+    // domain-separated so neither key can be confused for the other.
+    fn derive_keys(shared_secret: &[u8]) -> ([u8; HASH_BYTES_LEN], [u8; HASH_BYTES_LEN]) {
+        let enc_key = keccak_hash(&[shared_secret, b"ecies-encryption-key"].concat());
+        let mac_key = keccak_hash(&[shared_secret, b"ecies-mac-key"].concat());
+        (enc_key, mac_key)
+    }
+
+    // A keccak-driven CTR-mode keystream: block i is keccak(key || iv || i).
+    fn keystream(key: &[u8; HASH_BYTES_LEN], iv: &[u8; IV_BYTES_LEN], len: usize) -> Vec<u8> {
+        let mut out = Vec::with_capacity(len);
+        let mut counter: u64 = 0;
+        while out.len() < len {
+            let mut input = Vec::with_capacity(HASH_BYTES_LEN + IV_BYTES_LEN + 8);
+            input.extend_from_slice(key);
+            input.extend_from_slice(iv);
+            input.extend_from_slice(&counter.to_be_bytes());
+            out.extend_from_slice(&keccak_hash(&input));
+            counter += 1;
+        }
+        out.truncate(len);
+        out
+    }
+
+    fn xor_with_keystream(
+        key: &[u8; HASH_BYTES_LEN],
+        iv: &[u8; IV_BYTES_LEN],
+        data: &[u8],
+    ) -> Vec<u8> {
+        keystream(key, iv, data.len())
+            .iter()
+            .zip(data.iter())
+            .map(|(k, d)| k ^ d)
+            .collect()
+    }
+
+    fn mac(mac_key: &[u8; HASH_BYTES_LEN], iv: &[u8], ciphertext: &[u8]) -> [u8; HASH_BYTES_LEN] {
+        let mut input = Vec::with_capacity(mac_key.len() + iv.len() + ciphertext.len());
+        input.extend_from_slice(mac_key);
+        input.extend_from_slice(iv);
+        input.extend_from_slice(ciphertext);
+        keccak_hash(&input)
+    }
+
+    // Compare MAC tags in constant time so a timing side channel can't be
+    // used to forge a valid tag one byte at a time.
+    fn mac_eq(a: &[u8; HASH_BYTES_LEN], b: &[u8]) -> bool {
+        a.len() == b.len()
+            && a.iter()
+                .zip(b.iter())
+                .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+                == 0
+    }
+
+    /// Encrypt `plaintext` to `recipient_pk` (any of the encodings accepted
+    /// by [`super::pk2address`]).
+    pub fn encrypt(recipient_pk: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, StatusCodeEnum> {
+        let recipient = parse_pubkey(recipient_pk)?;
+
+        let mut rng = rand::thread_rng();
+        let (ephemeral_sk, ephemeral_pk) = SECP256K1_SIGN.generate_keypair(&mut rng);
+        let shared = secp256k1::ecdh::SharedSecret::new(&recipient, &ephemeral_sk);
+        let (enc_key, mac_key) = derive_keys(shared.as_ref());
+
+        let mut iv = [0u8; IV_BYTES_LEN];
+        rng.fill_bytes(&mut iv);
+
+        let ciphertext = xor_with_keystream(&enc_key, &iv, plaintext);
+        let tag = mac(&mac_key, &iv, &ciphertext);
+
+        let mut blob = Vec::with_capacity(
+            ECDSA_UNCOMPRESSED_PUBKEY_LEN + IV_BYTES_LEN + ciphertext.len() + MAC_BYTES_LEN,
+        );
+        blob.extend_from_slice(&ephemeral_pk.serialize_uncompressed());
+        blob.extend_from_slice(&iv);
+        blob.extend_from_slice(&ciphertext);
+        blob.extend_from_slice(&tag);
+        Ok(blob)
+    }
+
+    /// Decrypt a blob produced by [`encrypt`] using the recipient's 32-byte
+    /// secret key, rejecting it if the MAC doesn't match.
+    pub fn decrypt(recipient_sk: &[u8], blob: &[u8]) -> Result<Vec<u8>, StatusCodeEnum> {
+        let header_len = ECDSA_UNCOMPRESSED_PUBKEY_LEN + IV_BYTES_LEN;
+        if blob.len() < header_len + MAC_BYTES_LEN {
+            return Err(StatusCodeEnum::SigLenError);
+        }
+
+        let (ephemeral_pub_bytes, rest) = blob.split_at(ECDSA_UNCOMPRESSED_PUBKEY_LEN);
+        let (iv_bytes, rest) = rest.split_at(IV_BYTES_LEN);
+        let (ciphertext, tag) = rest.split_at(rest.len() - MAC_BYTES_LEN);
+
+        let ephemeral_pk = parse_pubkey(ephemeral_pub_bytes)?;
+        let sk = load_secret_key(recipient_sk, StatusCodeEnum::SigCheckError)?;
+        let shared = secp256k1::ecdh::SharedSecret::new(&ephemeral_pk, &sk);
+        let (enc_key, mac_key) = derive_keys(shared.as_ref());
+
+        let expected_tag = mac(&mac_key, iv_bytes, ciphertext);
+        if !mac_eq(&expected_tag, tag) {
+            return Err(StatusCodeEnum::SigCheckError);
+        }
+
+        let mut iv = [0u8; IV_BYTES_LEN];
+        iv.copy_from_slice(iv_bytes);
+        Ok(xor_with_keystream(&enc_key, &iv, ciphertext))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -230,7 +577,7 @@ mod tests {
         ),
         StatusCodeEnum,
     > {
-        let context = &SECP256K1;
+        let context = &SECP256K1_SIGN;
         let (sec_key, pub_key) = context.generate_keypair(&mut rand::thread_rng());
 
         let serialized = pub_key.serialize_uncompressed();
@@ -308,4 +655,147 @@ mod tests {
             Err(StatusCodeEnum::SigCheckError)
         );
     }
+
+    #[test]
+    fn test_high_s_rejected() {
+        let data: [u8; HASH_BYTES_LEN] = [
+            0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7,
+            0x03, 0xc0, 0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04,
+            0x5d, 0x85, 0xa4, 0x70,
+        ];
+
+        let (pubkey, privkey) = generate_keypair().unwrap();
+        let mut signature = sign_message(&pubkey, &privkey, &data).unwrap();
+        // secp256k1_sign always produces a low-S signature; force S above
+        // n/2 to simulate a malleable re-encoding and make sure it's rejected.
+        signature[32] |= 0x80;
+        assert_eq!(
+            recover_signature(&data, &signature),
+            Err(StatusCodeEnum::SigCheckError)
+        );
+    }
+
+    #[test]
+    fn test_rsv_roundtrip() {
+        let data: [u8; HASH_BYTES_LEN] = [
+            0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7,
+            0x03, 0xc0, 0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04,
+            0x5d, 0x85, 0xa4, 0x70,
+        ];
+
+        let (pubkey, privkey) = generate_keypair().unwrap();
+        let signature = sign_message(&pubkey, &privkey, &data).unwrap();
+
+        let (r, s, v) = signature_to_rsv(&signature).unwrap();
+        let rebuilt = signature_from_rsv(&r, &s, v).unwrap();
+        assert_eq!(rebuilt.to_vec(), signature);
+
+        // legacy v (27/28), as produced by signature_to_rsv
+        assert_eq!(
+            recover_signature_eth(&data, &r, &s, v as u64, None),
+            Ok(pubkey.clone())
+        );
+
+        // the crate's own bare recid (0/1) is accepted too
+        let recid = signature[SECP256K1_SIGNATURE_BYTES_LEN - 1];
+        assert_eq!(
+            recover_signature_eth(&data, &r, &s, recid as u64, None),
+            Ok(pubkey.clone())
+        );
+
+        let chain_id = 1u64;
+        let eip155_v = 35 + 2 * chain_id + recid as u64;
+        assert_eq!(
+            recover_signature_eth(&data, &r, &s, eip155_v, Some(chain_id)),
+            Ok(pubkey.clone())
+        );
+
+        // a large chain id pushes `v` past a single byte; make sure it's
+        // still carried correctly instead of silently truncating.
+        let large_chain_id = 1_000_000u64;
+        let large_eip155_v = 35 + 2 * large_chain_id + recid as u64;
+        assert!(large_eip155_v > u8::MAX as u64);
+        assert_eq!(
+            recover_signature_eth(&data, &r, &s, large_eip155_v, Some(large_chain_id)),
+            Ok(pubkey)
+        );
+    }
+
+    #[test]
+    fn test_compressed_pubkey() {
+        let data: [u8; HASH_BYTES_LEN] = [
+            0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7,
+            0x03, 0xc0, 0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04,
+            0x5d, 0x85, 0xa4, 0x70,
+        ];
+
+        let (_, privkey) = generate_keypair().unwrap();
+        let compressed_pk = sk2pk_compressed(&privkey).unwrap();
+        assert_eq!(compressed_pk.len(), ECDSA_COMPRESSED_PUBKEY_LEN);
+
+        let raw_pk = sk2pk(&privkey).unwrap();
+        assert_eq!(pk2address(&raw_pk), pk2address(&compressed_pk));
+
+        let mut uncompressed_pk = vec![0x04u8];
+        uncompressed_pk.extend_from_slice(&raw_pk);
+        assert_eq!(pk2address(&raw_pk), pk2address(&uncompressed_pk));
+
+        let signature = sign_message(&raw_pk, &privkey, &data).unwrap();
+        assert_eq!(
+            recover_signature_compressed(&data, &signature),
+            Ok(compressed_pk)
+        );
+    }
+
+    #[test]
+    fn test_invalid_privkey() {
+        let data: [u8; HASH_BYTES_LEN] = [
+            0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7,
+            0x03, 0xc0, 0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04,
+            0x5d, 0x85, 0xa4, 0x70,
+        ];
+
+        // zero is not a valid secp256k1 private key
+        let zero_key = [0u8; SECP256K1_PRIVKEY_BYTES_LEN];
+        assert_eq!(
+            sign_message(&[], &zero_key, &data),
+            Err(StatusCodeEnum::SignError)
+        );
+        // key-derivation failures are reported distinctly from signing failures
+        assert_eq!(sk2pk(&zero_key), Err(StatusCodeEnum::SigCheckError));
+        assert_eq!(sk2address(&zero_key), Err(StatusCodeEnum::SigCheckError));
+
+        // wrong length is not a valid secp256k1 private key either
+        let short_key = [0x01u8; SECP256K1_PRIVKEY_BYTES_LEN - 1];
+        assert_eq!(
+            sign_message(&[], &short_key, &data),
+            Err(StatusCodeEnum::SignError)
+        );
+    }
+
+    #[test]
+    fn test_ecies_roundtrip() {
+        let (pubkey, privkey) = generate_keypair().unwrap();
+        let plaintext = b"a confidential message".to_vec();
+
+        let blob = ecies::encrypt(&pubkey, &plaintext).unwrap();
+        assert_eq!(ecies::decrypt(&privkey, &blob).unwrap(), plaintext);
+
+        // tampering with the blob (here its last byte, part of the MAC tag)
+        // must be caught by the MAC check
+        let mut tampered = blob.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0xff;
+        assert_eq!(
+            ecies::decrypt(&privkey, &tampered),
+            Err(StatusCodeEnum::SigCheckError)
+        );
+
+        // a different recipient key must not be able to decrypt
+        let (_, other_privkey) = generate_keypair().unwrap();
+        assert_eq!(
+            ecies::decrypt(&other_privkey, &blob),
+            Err(StatusCodeEnum::SigCheckError)
+        );
+    }
 }